@@ -0,0 +1,74 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use async_graphql::{ErrorExtensions, Result as GraphQLResult};
+use oso::{Oso, PolarClass, ToPolar};
+
+/// The policy file loaded when no explicit path is configured, relative to the crate root.
+pub const DEFAULT_POLICY_PATH: &str = "policies/authorization.polar";
+
+/// Build an [`Oso`] instance with the application's resource classes registered and the
+/// Polar policy at `path` loaded.
+///
+/// Registration lives in [`register_classes`] so resolvers and tests share exactly the
+/// same class set. Policy loading is fail-fast: a malformed or missing `.polar` file is a
+/// startup error rather than a silent open door.
+pub fn init(path: impl AsRef<Path>) -> Result<Oso, Box<dyn Error>> {
+    let mut oso = Oso::new();
+    register_classes(&mut oso)?;
+    oso.load_files(vec![resolve_path(path.as_ref())])?;
+    Ok(oso)
+}
+
+/// Resolve a policy path against the crate root so startup does not depend on the process
+/// working directory; an absolute path is used as-is.
+fn resolve_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(path)
+    }
+}
+
+/// Register every type that appears as an actor or resource in the policy.
+///
+/// Domain entities register themselves here as they are introduced, so the policy can
+/// refer to them by their Rust type name.
+fn register_classes(oso: &mut Oso) -> Result<(), Box<dyn Error>> {
+    oso.register_class(
+        crate::users::Model::get_polar_class_builder()
+            .name("User")
+            .build(),
+    )?;
+    Ok(())
+}
+
+/// Authorize `action` by `actor` against `resource`, mapping a denial to a GraphQL error
+/// carrying a `FORBIDDEN` extension code.
+///
+/// Resolvers call this in place of hand-rolled permission checks, e.g.
+/// `authz::authorize(ctx.data::<Oso>()?, username, "read", user)?`.
+pub fn authorize<Actor, Resource>(
+    oso: &Oso,
+    actor: Actor,
+    action: &str,
+    resource: Resource,
+) -> GraphQLResult<()>
+where
+    Actor: ToPolar,
+    Resource: ToPolar,
+{
+    if oso.is_allowed(actor, action.to_string(), resource)? {
+        Ok(())
+    } else {
+        Err(forbidden(action))
+    }
+}
+
+/// A GraphQL error carrying a `FORBIDDEN` extension code for a denied `action`.
+pub fn forbidden(action: &str) -> async_graphql::Error {
+    async_graphql::Error::new(format!("not authorized to {action}"))
+        .extend_with(|_, ext| ext.set("code", "FORBIDDEN"))
+}
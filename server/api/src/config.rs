@@ -0,0 +1,158 @@
+use std::{env, error::Error, net::SocketAddr, sync::Arc};
+
+use flowist_auth::Authenticator;
+use serde::Deserialize;
+
+/// How bearer tokens are verified.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "lowercase", tag = "strategy")]
+pub enum JwtConfig {
+    /// Validate HS256 tokens against a static shared secret.
+    Secret { secret: String },
+    /// Validate RS256 tokens against the keys served by a remote JWKS endpoint.
+    Jwks { url: String },
+}
+
+/// Typed runtime configuration, merged from an optional TOML file and the environment.
+///
+/// Environment variables take precedence over the file so deployments can override a
+/// baked-in config without editing it. Every field has a development-friendly default,
+/// but [`Config::validate`] rejects a nonsensical combination before the server binds.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The sea-orm database connection URL.
+    pub database_url: String,
+    /// The host the server binds to.
+    pub host: String,
+    /// The port the server binds to.
+    pub port: u16,
+    /// Expected `iss` claim on incoming tokens.
+    pub jwt_issuer: String,
+    /// Expected `aud` claim on incoming tokens.
+    pub jwt_audience: String,
+    /// The token verification strategy.
+    pub jwt: JwtConfig,
+    /// Allowed CORS origins; an empty list disables cross-origin requests.
+    pub cors_origins: Vec<String>,
+    /// The `tracing` env-filter directive.
+    pub log_filter: String,
+    /// Path to the Oso policy file; relative paths resolve against the crate root.
+    pub policy_path: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            database_url: "sqlite::memory:".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 3000,
+            jwt_issuer: "flowist".to_string(),
+            jwt_audience: "flowist".to_string(),
+            jwt: JwtConfig::Secret {
+                secret: "insecure-development-secret".to_string(),
+            },
+            cors_origins: Vec::new(),
+            log_filter: "info".to_string(),
+            policy_path: crate::authz::DEFAULT_POLICY_PATH.to_string(),
+        }
+    }
+}
+
+impl Config {
+    /// Load configuration from an optional TOML file pointed at by `FLOWIST_CONFIG`,
+    /// overlaid by the process environment. Variables from an optional `.env` file are
+    /// added to the environment first (without replacing ones already set), so they too
+    /// take precedence over the TOML file.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        // Populate the environment from a local `.env` if one is present; absence is fine.
+        let _ = dotenvy::dotenv();
+
+        let mut config = match env::var("FLOWIST_CONFIG") {
+            Ok(path) => toml::from_str(&std::fs::read_to_string(path)?)?,
+            Err(_) => Config::default(),
+        };
+        config.merge_env();
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Overlay any recognised environment variables onto the current values.
+    fn merge_env(&mut self) {
+        if let Ok(value) = env::var("DATABASE_URL") {
+            self.database_url = value;
+        }
+        if let Ok(value) = env::var("HOST") {
+            self.host = value;
+        }
+        if let Ok(value) = env::var("PORT") {
+            if let Ok(port) = value.parse() {
+                self.port = port;
+            }
+        }
+        if let Ok(value) = env::var("JWT_ISSUER") {
+            self.jwt_issuer = value;
+        }
+        if let Ok(value) = env::var("JWT_AUDIENCE") {
+            self.jwt_audience = value;
+        }
+        if let Ok(url) = env::var("JWKS_URL") {
+            self.jwt = JwtConfig::Jwks { url };
+        } else if let Ok(secret) = env::var("JWT_SECRET") {
+            self.jwt = JwtConfig::Secret { secret };
+        }
+        if let Ok(value) = env::var("CORS_ORIGINS") {
+            self.cors_origins = value
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Ok(value) = env::var("LOG_FILTER") {
+            self.log_filter = value;
+        }
+        if let Ok(value) = env::var("POLICY_PATH") {
+            self.policy_path = value;
+        }
+    }
+
+    /// Reject a configuration that cannot produce a working server.
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.database_url.is_empty() {
+            return Err("database_url must not be empty".into());
+        }
+        if self.port == 0 {
+            return Err("port must be a non-zero TCP port".into());
+        }
+        // Surface an unparsable bind address now rather than at `run()`.
+        let _ = self.socket_addr()?;
+        match &self.jwt {
+            JwtConfig::Secret { secret } if secret.is_empty() => {
+                Err("jwt secret must not be empty".into())
+            }
+            JwtConfig::Jwks { url } if url.is_empty() => {
+                Err("jwks url must not be empty".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The resolved address the server binds to.
+    pub fn socket_addr(&self) -> Result<SocketAddr, Box<dyn Error>> {
+        Ok(format!("{}:{}", self.host, self.port).parse()?)
+    }
+
+    /// Build the [`Authenticator`] described by this configuration.
+    pub fn authenticator(&self) -> Arc<Authenticator> {
+        let authenticator = match &self.jwt {
+            JwtConfig::Secret { secret } => {
+                Authenticator::with_secret(secret.as_bytes(), &self.jwt_issuer, &self.jwt_audience)
+            }
+            JwtConfig::Jwks { url } => {
+                Authenticator::with_jwks(url.clone(), &self.jwt_issuer, &self.jwt_audience)
+            }
+        };
+        Arc::new(authenticator)
+    }
+}
@@ -1,28 +1,164 @@
 use std::{error::Error, sync::Arc};
 
-use async_graphql::{EmptySubscription, MergedObject, Schema};
+use async_graphql::{
+    dataloader::DataLoader, Context as GraphQLContext, Enum, MergedObject, Object, Schema,
+    SimpleObject, Subscription,
+};
+use flowist_auth::Subject;
+use oso::Oso;
+use tokio::sync::broadcast;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
 
-use crate::Context;
+use crate::{
+    authz,
+    users::{self, CreateUserInput, UpdateProfileInput, UserLoader, UsersService},
+    Context,
+};
+
+/// The default capacity of the domain-event broadcast channel.
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// The kind of change a [`EntityEvent`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+pub enum EntityAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// A domain event published by a mutation and delivered to subscribers.
+///
+/// Events are deliberately entity-agnostic — `kind` names the resource (e.g. `"User"`) and
+/// `id` its identifier — so new domain entities can publish without extending the schema.
+#[derive(Debug, Clone, SimpleObject)]
+pub struct EntityEvent {
+    pub kind: String,
+    pub id: String,
+    pub action: EntityAction,
+}
 
 #[derive(Default)]
 pub struct UsersQuery {}
 
+#[Object]
+impl UsersQuery {
+    /// Fetch a user by id, gated by a `read` check on the `User` resource.
+    async fn get_user(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        id: i32,
+    ) -> async_graphql::Result<Option<users::Model>> {
+        let user = ctx
+            .data::<DataLoader<UserLoader>>()?
+            .load_one(id)
+            .await
+            .map_err(|err| async_graphql::Error::new(err.to_string()))?;
+        if let Some(ref user) = user {
+            let actor = current_username(ctx);
+            authz::authorize(ctx.data::<Oso>()?, actor, "read", user.clone())?;
+        }
+        Ok(user)
+    }
+
+    /// Resolve the user authenticated by the request's [`Subject`], if any.
+    async fn get_current_user(
+        &self,
+        ctx: &GraphQLContext<'_>,
+    ) -> async_graphql::Result<Option<users::Model>> {
+        // The handler resolves the current user once per request and places it in context.
+        Ok(ctx.data::<Option<users::Model>>()?.clone())
+    }
+}
+
 #[derive(Default)]
 pub struct UsersMutation {}
 
+#[Object]
+impl UsersMutation {
+    /// Create a new user and publish a `Created` event.
+    async fn create_user(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        input: CreateUserInput,
+    ) -> async_graphql::Result<users::Model> {
+        let user = ctx.data::<UsersService>()?.create(input).await?;
+        publish(ctx, &user, EntityAction::Created);
+        Ok(user)
+    }
+
+    /// Update the current user's profile and publish an `Updated` event.
+    async fn update_profile(
+        &self,
+        ctx: &GraphQLContext<'_>,
+        input: UpdateProfileInput,
+    ) -> async_graphql::Result<users::Model> {
+        let current = ctx
+            .data::<Option<users::Model>>()?
+            .as_ref()
+            .ok_or_else(|| async_graphql::Error::new("not authenticated"))?;
+        let user = ctx.data::<UsersService>()?.update(current.id, input).await?;
+        publish(ctx, &user, EntityAction::Updated);
+        Ok(user)
+    }
+}
+
+/// The username carried by the request's [`Subject`], or an empty string when anonymous.
+fn current_username(ctx: &GraphQLContext<'_>) -> String {
+    match ctx.data_opt::<Subject>() {
+        Some(Subject(Some(username))) => username.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Publish an [`EntityEvent`] for `user` onto the broadcast bus, ignoring a send with no
+/// active subscribers.
+fn publish(ctx: &GraphQLContext<'_>, user: &users::Model, action: EntityAction) {
+    if let Ok(sender) = ctx.data::<broadcast::Sender<EntityEvent>>() {
+        let _ = sender.send(EntityEvent {
+            kind: "User".to_string(),
+            id: user.id.to_string(),
+            action,
+        });
+    }
+}
+
 #[derive(MergedObject, Default)]
 pub struct Query(UsersQuery);
 
 #[derive(MergedObject, Default)]
 pub struct Mutation(UsersMutation);
 
-pub type GraphQLSchema = Schema<Query, Mutation, EmptySubscription>;
+/// The subscription root, delivering domain events off the broadcast bus.
+pub struct Subscription;
+
+#[Subscription]
+impl Subscription {
+    /// Stream every [`EntityEvent`] published after the subscription is opened.
+    async fn events(
+        &self,
+        ctx: &GraphQLContext<'_>,
+    ) -> async_graphql::Result<impl Stream<Item = EntityEvent>> {
+        // Access-control the stream like any query: only an authenticated subject, threaded
+        // in via the WS connection-init payload, may open it.
+        if !matches!(ctx.data_opt::<Subject>(), Some(Subject(Some(_)))) {
+            return Err(authz::forbidden("subscribe"));
+        }
+        let sender = ctx.data::<broadcast::Sender<EntityEvent>>()?;
+        // Lagging subscribers miss events rather than stall the whole bus, so drop the
+        // `Err(Lagged)` frames that `BroadcastStream` surfaces.
+        Ok(BroadcastStream::new(sender.subscribe()).filter_map(|event| event.ok()))
+    }
+}
+
+pub type GraphQLSchema = Schema<Query, Mutation, Subscription>;
 
 pub fn create_schema(ctx: Arc<Context>) -> Result<GraphQLSchema, Box<dyn Error>> {
-    // Inject the initialized seervices into the Schema instance
+    // Inject the initialized services into the Schema instance
     Ok(
-        Schema::build(Query::default(), Mutation::default(), EmptySubscription)
+        Schema::build(Query::default(), Mutation::default(), Subscription)
             .data(ctx.oso.clone())
+            .data(ctx.events.clone())
+            .data(ctx.users.clone())
             .finish(),
     )
 }
@@ -1,35 +1,53 @@
-use std::{error::Error, net::SocketAddr, sync::Arc};
+use std::{error::Error, sync::Arc};
 
-use async_graphql::http::GraphiQLSource;
-use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use async_graphql::{dataloader::DataLoader, http::GraphiQLSource};
+use async_graphql_axum::{GraphQLProtocol, GraphQLRequest, GraphQLResponse, GraphQLWebSocket};
 use axum::{
-    response::{Html, IntoResponse},
+    extract::WebSocketUpgrade,
+    http::HeaderValue,
+    response::{Html, IntoResponse, Response},
     routing::{get, IntoMakeService},
     Extension, Router,
 };
-use flowist_auth::Subject;
-use graphql::GraphQLSchema;
+use flowist_auth::{Authenticator, Subject};
+use graphql::{EntityEvent, GraphQLSchema, EVENT_CHANNEL_CAPACITY};
 use hyper::{server::conn::AddrIncoming, Server};
 use oso::Oso;
 use sea_orm::DatabaseConnection;
 use serde_json::json;
-use tower_http::trace::TraceLayer;
+use tokio::sync::broadcast;
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing_subscriber::{prelude::__tracing_subscriber_SubscriberExt, util::SubscriberInitExt};
 
+use crate::config::Config;
+
+pub mod authz;
+pub mod config;
 pub mod graphql;
+pub mod users;
+
+use users::UsersService;
 
 pub struct Context {
     // The database connections
     pub db: Arc<DatabaseConnection>,
     // The authorization library
     pub oso: Oso,
+    // The domain-event bus backing GraphQL subscriptions
+    pub events: broadcast::Sender<EntityEvent>,
+    // The users persistence service
+    pub users: UsersService,
 }
 
 impl Context {
-    pub async fn init() -> Result<Self, Box<dyn Error>> {
+    pub async fn init(config: &Config) -> Result<Self, Box<dyn Error>> {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        let db = Arc::new(sea_orm::Database::connect(&config.database_url).await?);
         Ok(Self {
-            oso: Oso::new(),
-            db: Arc::new(sea_orm::Database::connect("").await?),
+            oso: authz::init(&config.policy_path)?,
+            users: UsersService::new(db.clone()),
+            db,
+            events,
         })
     }
 }
@@ -56,35 +74,81 @@ pub async fn graphql_handler(
 ) -> GraphQLResponse {
     // Retrieve the request User, if username is present
     let user = if let Subject(Some(ref username)) = sub {
-        None
-        // ctx.users
-        //     .get_by_username(username, &true)
-        //     .await
-        //     .unwrap_or(None)
+        ctx.users
+            .get_by_username(username)
+            .await
+            .unwrap_or(None)
     } else {
         None
     };
-    // Add the Subject and optional User to the context
-    let request = req.into_inner().data(sub).data(None);
+    // Per-request DataLoaders so the cache scope is the request, not the process.
+    let user_loader = DataLoader::new(users::UserLoader::new(ctx.db.clone()), tokio::spawn);
+    // Add the Subject, optional User and loaders to the context
+    let request = req.into_inner().data(sub).data(user).data(user_loader);
     schema.execute(request).await.into()
 }
 
-fn router() -> Router {
-    Router::new()
+/// Handle GraphQL subscriptions over a WebSocket connection.
+///
+/// The authenticated [`Subject`] is carried in the connection-init payload rather than an
+/// `Authorization` header, so the WS transport is access-controlled just like the HTTP
+/// endpoint. A missing or anonymous payload yields `Subject(None)`; a supplied-but-invalid
+/// token closes the connection init.
+pub async fn graphql_ws_handler(
+    Extension(schema): Extension<GraphQLSchema>,
+    Extension(authenticator): Extension<Arc<Authenticator>>,
+    protocol: GraphQLProtocol,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    upgrade.on_upgrade(move |socket| {
+        GraphQLWebSocket::new(socket, schema.clone(), protocol)
+            .on_connection_init(move |payload| async move {
+                let subject = match payload.get("Authorization").and_then(|value| value.as_str()) {
+                    Some(bearer) => authenticator
+                        .subject_from_bearer(bearer)
+                        .await
+                        .map_err(|err| async_graphql::Error::new(err.to_string()))?,
+                    None => Subject(None),
+                };
+                let mut data = async_graphql::Data::default();
+                data.insert(subject);
+                Ok(data)
+            })
+            .serve()
+    })
+}
+
+/// Build the CORS layer from the configured allowed origins.
+fn cors_layer(origins: &[String]) -> Result<CorsLayer, Box<dyn Error>> {
+    let origins = origins
+        .iter()
+        .map(|origin| origin.parse::<HeaderValue>())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(CorsLayer::new().allow_origin(origins))
+}
+
+/// Assemble the application router, injecting the schema, [`Context`] and [`Authenticator`]
+/// as `Extension`s so the handlers can reach them.
+fn router(context: Arc<Context>, config: &Config) -> Result<Router, Box<dyn Error>> {
+    let schema = graphql::create_schema(context.clone())?;
+    Ok(Router::new()
         .route("/", get(|| async { "Hello, World!" }))
         .route("/graphql", get(graphiql).post(graphql_handler))
+        .route("/ws", get(graphql_ws_handler))
+        .layer(Extension(schema))
+        .layer(Extension(context))
+        .layer(Extension(config.authenticator()))
         // We can still add middleware
-        .layer(TraceLayer::new_for_http())
+        .layer(cors_layer(&config.cors_origins)?)
+        .layer(TraceLayer::new_for_http()))
 }
 
 pub async fn run(
     context: Arc<Context>,
+    config: Config,
 ) -> Result<Server<AddrIncoming, IntoMakeService<Router>>, Box<dyn Error>> {
-    let port = "";
-
-    let router = router();
-
-    let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
+    let addr = config.socket_addr()?;
+    let router = router(context, &config)?;
     let server = axum::Server::bind(&addr).serve(router.into_make_service());
 
     Ok(server)
@@ -92,15 +156,19 @@ pub async fn run(
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
+    let config = Config::load()?;
+
     tracing_subscriber::registry()
         .with(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()),
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| config.log_filter.clone().into()),
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // let config = get_config();
-    let context = Arc::new(Context::init().await?);
+    let context = Arc::new(Context::init(&config).await?);
+
+    run(context, config).await?.await?;
 
     Ok(())
 }
@@ -115,9 +183,16 @@ mod tests {
     use serde_json::{json, Value};
     use tower::ServiceExt;
 
+    /// Build a router backed by a default (in-memory) configuration for tests.
+    async fn test_router() -> Router {
+        let config = Config::default();
+        let context = Arc::new(Context::init(&config).await.unwrap());
+        router(context, &config).unwrap()
+    }
+
     #[tokio::test]
     async fn test_hello_world() {
-        let app = router();
+        let app = test_router().await;
 
         let response = app
             .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
@@ -131,17 +206,17 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn json() {
-        let app = router();
+    async fn graphql_query() {
+        let app = test_router().await;
 
         let response = app
             .oneshot(
                 Request::builder()
                     .method(http::Method::POST)
-                    .uri("/json")
+                    .uri("/graphql")
                     .header(http::header::CONTENT_TYPE, mime::APPLICATION_JSON.as_ref())
                     .body(Body::from(
-                        serde_json::to_vec(&json!([1, 2, 3, 4])).unwrap(),
+                        serde_json::to_vec(&json!({ "query": "{ __typename }" })).unwrap(),
                     ))
                     .unwrap(),
             )
@@ -152,12 +227,12 @@ mod tests {
 
         let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
         let body: Value = serde_json::from_slice(&body).unwrap();
-        assert_eq!(body, json!({ "data": [1, 2, 3, 4] }));
+        assert_eq!(body, json!({ "data": { "__typename": "Query" } }));
     }
 
     #[tokio::test]
     async fn not_found() {
-        let app = router();
+        let app = test_router().await;
 
         let response = app
             .oneshot(
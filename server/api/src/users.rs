@@ -0,0 +1,198 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::{dataloader::Loader, InputObject, SimpleObject};
+use async_trait::async_trait;
+use oso::PolarClass;
+use sea_orm::{entity::prelude::*, ActiveValue::Set, DatabaseConnection};
+
+/// A persisted user account.
+///
+/// The struct doubles as the sea-orm entity model, the GraphQL `User` output type, and the
+/// Oso `User` resource, so the same shape flows from the database to the policy engine.
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, SimpleObject, PolarClass)]
+#[sea_orm(table_name = "users")]
+#[graphql(name = "User")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    #[polar(attribute)]
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Fields accepted when creating a user.
+#[derive(Debug, InputObject)]
+pub struct CreateUserInput {
+    pub username: String,
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+}
+
+/// Fields accepted when updating a user's profile; `None` leaves a field unchanged.
+#[derive(Debug, InputObject)]
+pub struct UpdateProfileInput {
+    pub display_name: Option<String>,
+    pub email: Option<String>,
+}
+
+/// Persistence operations for [`Model`], backed by a shared database connection.
+#[derive(Clone)]
+pub struct UsersService {
+    db: Arc<DatabaseConnection>,
+}
+
+impl UsersService {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+
+    /// Look a user up by primary key.
+    pub async fn get_by_id(&self, id: i32) -> Result<Option<Model>, DbErr> {
+        Entity::find_by_id(id).one(self.db.as_ref()).await
+    }
+
+    /// Look a user up by their unique username.
+    pub async fn get_by_username(&self, username: &str) -> Result<Option<Model>, DbErr> {
+        Entity::find()
+            .filter(Column::Username.eq(username))
+            .one(self.db.as_ref())
+            .await
+    }
+
+    /// Insert a new user and return the persisted model.
+    pub async fn create(&self, input: CreateUserInput) -> Result<Model, DbErr> {
+        ActiveModel {
+            username: Set(input.username),
+            display_name: Set(input.display_name),
+            email: Set(input.email),
+            ..Default::default()
+        }
+        .insert(self.db.as_ref())
+        .await
+    }
+
+    /// Apply a profile update to the user with `id` and return the persisted model.
+    pub async fn update(&self, id: i32, input: UpdateProfileInput) -> Result<Model, DbErr> {
+        let mut user: ActiveModel = Entity::find_by_id(id)
+            .one(self.db.as_ref())
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("user {id}")))?
+            .into();
+        if let Some(display_name) = input.display_name {
+            user.display_name = Set(Some(display_name));
+        }
+        if let Some(email) = input.email {
+            user.email = Set(Some(email));
+        }
+        user.update(self.db.as_ref()).await
+    }
+}
+
+/// Batches user lookups by id into a single `WHERE id IN (...)` query.
+///
+/// A fresh instance is created per request so the `DataLoader` cache is request-scoped,
+/// matching the lifetime of the other data injected into the GraphQL context.
+pub struct UserLoader {
+    db: Arc<DatabaseConnection>,
+}
+
+impl UserLoader {
+    pub fn new(db: Arc<DatabaseConnection>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl Loader<i32> for UserLoader {
+    type Value = Model;
+    type Error = Arc<DbErr>;
+
+    async fn load(&self, keys: &[i32]) -> Result<HashMap<i32, Model>, Self::Error> {
+        let users = Entity::find()
+            .filter(Column::Id.is_in(keys.iter().copied()))
+            .all(self.db.as_ref())
+            .await
+            .map_err(Arc::new)?;
+        Ok(users.into_iter().map(|user| (user.id, user)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_graphql::dataloader::DataLoader;
+    use sea_orm::{DatabaseBackend, MockDatabase};
+
+    fn model(id: i32, username: &str) -> Model {
+        Model {
+            id,
+            username: username.to_string(),
+            display_name: None,
+            email: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn loads_many_users_in_a_single_query() {
+        let conn = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([vec![
+                Model {
+                    id: 1,
+                    username: "ada".to_string(),
+                    display_name: None,
+                    email: None,
+                },
+                Model {
+                    id: 2,
+                    username: "grace".to_string(),
+                    display_name: None,
+                    email: None,
+                },
+            ]])
+            .into_connection();
+        let db = Arc::new(conn);
+
+        let loader = UserLoader::new(db.clone());
+        let loaded = loader.load(&[1, 2]).await.unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[&1].username, "ada");
+
+        // The whole batch must resolve through one `WHERE id IN (...)` statement.
+        drop(loader);
+        let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+        assert_eq!(log.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn dataloader_coalesces_load_one_into_one_query() {
+        let conn = MockDatabase::new(DatabaseBackend::Sqlite)
+            .append_query_results([vec![
+                model(1, "ada"),
+                model(2, "grace"),
+                model(3, "edsger"),
+            ]])
+            .into_connection();
+        let db = Arc::new(conn);
+
+        let loader = DataLoader::new(UserLoader::new(db.clone()), tokio::spawn);
+
+        // Concurrent `load_one` calls — as separate nested resolvers would issue them —
+        // must be coalesced into a single batched query rather than one round-trip each.
+        let (a, b, c) = tokio::join!(loader.load_one(1), loader.load_one(2), loader.load_one(3));
+        assert_eq!(a.unwrap().unwrap().username, "ada");
+        assert_eq!(b.unwrap().unwrap().username, "grace");
+        assert_eq!(c.unwrap().unwrap().username, "edsger");
+
+        drop(loader);
+        let log = Arc::try_unwrap(db).unwrap().into_transaction_log();
+        assert_eq!(log.len(), 1);
+    }
+}
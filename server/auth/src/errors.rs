@@ -0,0 +1,38 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+/// Errors that can occur while extracting and validating a [`Subject`](crate::Subject).
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    /// The `Authorization` header was present but not a well-formed `Bearer` token.
+    #[error("malformed Authorization header")]
+    MalformedHeader,
+
+    /// No verification keys have been configured, so tokens cannot be validated.
+    #[error("authentication is not configured")]
+    Misconfigured,
+
+    /// The JWKS document could not be fetched or parsed.
+    #[error("unable to retrieve signing keys: {0}")]
+    Jwks(String),
+
+    /// The token carried no `kid`, or one that is not present in the JWKS document.
+    #[error("no signing key found for token")]
+    UnknownKey,
+
+    /// The token failed signature or claim validation.
+    #[error("invalid token: {0}")]
+    InvalidToken(#[from] jsonwebtoken::errors::Error),
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        // A present-but-invalid token is always a client error. Misconfiguration and
+        // JWKS retrieval failures are server-side, but surfacing anything other than
+        // `401` to an unauthenticated caller would leak operational detail, so every
+        // failure to authenticate a supplied token maps to `Unauthorized`.
+        (StatusCode::UNAUTHORIZED, self.to_string()).into_response()
+    }
+}
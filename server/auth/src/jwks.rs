@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use jsonwebtoken::DecodingKey;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+use crate::errors::AuthError;
+
+/// A single key entry from a remote JWKS document.
+#[derive(Debug, Deserialize)]
+struct Jwk {
+    kid: String,
+    /// RSA modulus, base64url encoded.
+    n: String,
+    /// RSA exponent, base64url encoded.
+    e: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+/// Fetches and caches the `DecodingKey`s served by a remote JWKS endpoint, keyed by `kid`.
+///
+/// The cache is populated lazily: a lookup for an unknown `kid` triggers a single refresh
+/// of the whole document before giving up, which transparently picks up rotated keys.
+#[derive(Debug)]
+pub struct JwksCache {
+    url: String,
+    keys: RwLock<HashMap<String, DecodingKey>>,
+}
+
+impl JwksCache {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            keys: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Return the decoding key for `kid`, refreshing the cache on a miss before failing.
+    pub async fn key_for(&self, kid: &str) -> Result<DecodingKey, AuthError> {
+        if let Some(key) = self.keys.read().await.get(kid).cloned() {
+            return Ok(key);
+        }
+
+        self.refresh().await?;
+
+        self.keys
+            .read()
+            .await
+            .get(kid)
+            .cloned()
+            .ok_or(AuthError::UnknownKey)
+    }
+
+    /// Fetch the JWKS document and replace the cached keys.
+    async fn refresh(&self) -> Result<(), AuthError> {
+        let set: JwkSet = reqwest::get(&self.url)
+            .await
+            .map_err(|err| AuthError::Jwks(err.to_string()))?
+            .json()
+            .await
+            .map_err(|err| AuthError::Jwks(err.to_string()))?;
+
+        let mut fresh = HashMap::with_capacity(set.keys.len());
+        for jwk in set.keys {
+            let key = DecodingKey::from_rsa_components(&jwk.n, &jwk.e)
+                .map_err(|err| AuthError::Jwks(err.to_string()))?;
+            fresh.insert(jwk.kid, key);
+        }
+
+        *self.keys.write().await = fresh;
+        Ok(())
+    }
+}
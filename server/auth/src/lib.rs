@@ -1,3 +1,124 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+
+mod errors;
+mod jwks;
+
+pub use errors::AuthError;
+pub use jwks::JwksCache;
+
 /// The token's Subject claim, which corresponds with the username
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Subject(pub Option<String>);
+
+/// The registered claims we validate and read out of a verified token.
+#[derive(Debug, Deserialize)]
+struct Claims {
+    /// The username carried by the token.
+    sub: String,
+}
+
+/// Where the [`Authenticator`] sources its verification keys from.
+enum Keys {
+    /// A single static HS256 shared secret.
+    Secret(DecodingKey),
+    /// RS256 keys fetched from a remote JWKS endpoint, looked up by `kid`.
+    Jwks(JwksCache),
+}
+
+/// Validates bearer tokens and yields the [`Subject`] they authenticate.
+///
+/// An instance is attached to the router as an `Extension` and pulled back out of the
+/// request when extracting a [`Subject`], so both the HS256 and RS256 strategies share
+/// the same extraction path. The configured issuer and audience are enforced on every
+/// token alongside the mandatory `exp` check.
+pub struct Authenticator {
+    keys: Keys,
+    validation: Validation,
+}
+
+impl Authenticator {
+    /// Verify tokens against a static HS256 shared secret.
+    pub fn with_secret(secret: &[u8], issuer: &str, audience: &str) -> Self {
+        Self {
+            keys: Keys::Secret(DecodingKey::from_secret(secret)),
+            validation: Self::validation(Algorithm::HS256, issuer, audience),
+        }
+    }
+
+    /// Verify tokens against RS256 keys served by a remote JWKS endpoint.
+    pub fn with_jwks(url: String, issuer: &str, audience: &str) -> Self {
+        Self {
+            keys: Keys::Jwks(JwksCache::new(url)),
+            validation: Self::validation(Algorithm::RS256, issuer, audience),
+        }
+    }
+
+    fn validation(algorithm: Algorithm, issuer: &str, audience: &str) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        validation.set_issuer(&[issuer]);
+        validation.set_audience(&[audience]);
+        validation
+    }
+
+    /// Authenticate a raw `Bearer <token>` value, as supplied in a WebSocket
+    /// connection-init payload, into the [`Subject`] it identifies.
+    pub async fn subject_from_bearer(&self, value: &str) -> Result<Subject, AuthError> {
+        let token = value
+            .strip_prefix("Bearer ")
+            .ok_or(AuthError::MalformedHeader)?;
+        let claims = self.decode(token).await?;
+        Ok(Subject(Some(claims.sub)))
+    }
+
+    /// Decode and validate `token`, returning its claims on success.
+    async fn decode(&self, token: &str) -> Result<Claims, AuthError> {
+        let key = match &self.keys {
+            Keys::Secret(key) => key.clone(),
+            Keys::Jwks(cache) => {
+                let kid = decode_header(token)?.kid.ok_or(AuthError::UnknownKey)?;
+                cache.key_for(&kid).await?
+            }
+        };
+
+        Ok(decode::<Claims>(token, &key, &self.validation)?.claims)
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Subject
+where
+    S: Send + Sync,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // No credentials at all is an anonymous request, not an error; resolvers decide
+        // whether that is allowed. Only a supplied-but-invalid token is rejected.
+        let header = match parts.headers.get(AUTHORIZATION) {
+            Some(header) => header,
+            None => return Ok(Subject(None)),
+        };
+
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(AuthError::MalformedHeader)?;
+
+        let authenticator = parts
+            .extensions
+            .get::<Arc<Authenticator>>()
+            .ok_or(AuthError::Misconfigured)?;
+
+        let claims = authenticator.decode(token).await?;
+        Ok(Subject(Some(claims.sub)))
+    }
+}